@@ -0,0 +1,198 @@
+use super::config::Config;
+use super::db;
+use super::film::{Film, Showtime};
+
+use std::error::Error;
+use std::fs;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+
+/// Default number of attempts `sync_file` makes before giving up on a
+/// cinema, matching the retry budget of comparable download tools.
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Base delay for the exponential backoff between retries: `base_delay * 2^(attempt-1)`.
+const DEFAULT_BASE_DELAY: StdDuration = StdDuration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct Market {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cinema {
+    pub id: String,
+    pub name: String,
+    pub market: Market,
+    pub feed_generated: DateTime<Utc>,
+}
+
+impl Cinema {
+    /// The built-in list of cinemas that ship with the tool. This is used
+    /// as the default universe for `cinema`/`get-all`/`watch` unless
+    /// `--local`/`--update-only` selects the locally cached cinemas.
+    pub fn list() -> Vec<Cinema> {
+        vec![
+            Cinema {
+                id: String::from("004"),
+                name: String::from("Alamo Drafthouse Village"),
+                market: Market { id: String::from("austin"), name: String::from("Austin") },
+                feed_generated: Self::epoch(),
+            },
+            Cinema {
+                id: String::from("008"),
+                name: String::from("Alamo Drafthouse Ritz"),
+                market: Market { id: String::from("austin"), name: String::from("Austin") },
+                feed_generated: Self::epoch(),
+            },
+        ]
+    }
+
+    fn epoch() -> DateTime<Utc> {
+        DateTime::from_utc(chrono::NaiveDateTime::from_timestamp(0, 0), Utc)
+    }
+
+    /// Resolves user input (an existing cinema ID, or a case-insensitive
+    /// substring of a cinema's name) to a canonical cinema ID.
+    pub fn to_cinema_id(input: &str) -> Result<String, Box<dyn Error>> {
+        if Self::list().iter().any(|c| c.id == input) {
+            return Ok(String::from(input));
+        }
+
+        let needle = input.to_lowercase();
+
+        match Self::list().into_iter().find(|c| c.name.to_lowercase().contains(&needle)) {
+            Some(cinema) => Ok(cinema.id),
+            None => Ok(String::from(input)),
+        }
+    }
+
+    pub fn get_file_path_for(config: &Config, cinema_id: &str) -> std::path::PathBuf {
+        db::calendar_path_for_cinema_id(config, cinema_id)
+    }
+
+    /// Downloads the calendar feed for `cinema_id` and writes it to the
+    /// local cache, returning the parsed cinema/films. Retries transient
+    /// network failures (connection errors, timeouts, 5xx responses) with
+    /// the default attempt count/backoff; see `sync_file_with_retry` to
+    /// tune those.
+    pub fn sync_file(config: &Config, cinema_id: &str) -> Result<(Cinema, Vec<Film>), Box<dyn Error>> {
+        Self::sync_file_with_retry(config, cinema_id, DEFAULT_MAX_ATTEMPTS, DEFAULT_BASE_DELAY)
+    }
+
+    /// Like `sync_file`, but lets the caller tune the retry budget:
+    /// `max_attempts` total tries, with exponential backoff starting at
+    /// `base_delay` (`base_delay * 2^(attempt-1)` between tries). 4xx
+    /// responses are treated as permanent and returned immediately.
+    pub fn sync_file_with_retry(config: &Config, cinema_id: &str, max_attempts: u32, base_delay: StdDuration) -> Result<(Cinema, Vec<Film>), Box<dyn Error>> {
+        let url = format!("https://feeds.drafthouse.com/adcff/{}.calendar.json", cinema_id);
+
+        let mut attempt = 1;
+
+        loop {
+            match reqwest::blocking::get(&url).and_then(|response| response.error_for_status()).and_then(|response| response.text()) {
+                Ok(body) => {
+                    let path = db::calendar_path_for_cinema_id(config, cinema_id);
+
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+
+                    fs::write(&path, &body)?;
+
+                    return Self::from_calendar_file(path.to_str().unwrap());
+                },
+                Err(error) => {
+                    if attempt >= max_attempts || !is_retryable(&error) {
+                        return Err(Box::new(error));
+                    }
+
+                    let delay = base_delay * 2u32.pow(attempt - 1);
+                    thread::sleep(delay);
+
+                    attempt += 1;
+                },
+            }
+        }
+    }
+
+    pub fn from_calendar_file(path: &str) -> Result<(Cinema, Vec<Film>), Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        let v: Value = serde_json::from_str(&contents)?;
+
+        let cinema_json = &v["Calendar"]["Cinemas"][0];
+
+        let feed_generated = v["Calendar"]["FeedGenerated"].as_str()
+            .and_then(parse_show_time)
+            .unwrap_or_else(Self::epoch);
+
+        let cinema = Cinema {
+            id: cinema_json["CinemaId"].as_str().unwrap_or_default().to_string(),
+            name: cinema_json["CinemaName"].as_str().unwrap_or_default().to_string(),
+            market: Market {
+                id: cinema_json["Market"]["MarketId"].as_str().unwrap_or_default().to_string(),
+                name: cinema_json["Market"]["MarketName"].as_str().unwrap_or_default().to_string(),
+            },
+            feed_generated,
+        };
+
+        let films = cinema_json["Films"]
+            .as_array()
+            .map(|films| films.iter().map(parse_film).collect())
+            .unwrap_or_default();
+
+        Ok((cinema, films))
+    }
+}
+
+fn parse_film(film_json: &Value) -> Film {
+    let showtimes = film_json["Sessions"]
+        .as_array()
+        .map(|sessions| sessions.iter().filter_map(parse_session).collect())
+        .unwrap_or_default();
+
+    Film {
+        id: film_json["FilmId"].as_str().unwrap_or_default().to_string(),
+        name: film_json["FilmName"].as_str().unwrap_or_default().to_string(),
+        show_type: film_json["ShowType"].as_str().unwrap_or_default().to_string(),
+        showtimes,
+    }
+}
+
+fn parse_session(session_json: &Value) -> Option<Showtime> {
+    let show_time = session_json["ShowTime"].as_str()?;
+    let starts_at = parse_show_time(show_time)?;
+
+    let run_time_minutes = session_json["RunTime"].as_i64().unwrap_or(120);
+
+    Some(Showtime {
+        session_id: session_json["SessionId"].as_str().unwrap_or_default().to_string(),
+        starts_at,
+        ends_at: starts_at + Duration::minutes(run_time_minutes),
+    })
+}
+
+/// Connection errors, timeouts, and 5xx responses are worth retrying; 4xx
+/// responses mean the request itself is bad and won't succeed on retry.
+fn is_retryable(error: &reqwest::Error) -> bool {
+    if error.is_connect() || error.is_timeout() {
+        return true;
+    }
+
+    match error.status() {
+        Some(status) => status.is_server_error(),
+        None => false,
+    }
+}
+
+fn parse_show_time(show_time: &str) -> Option<DateTime<Utc>> {
+    let with_offset = format!("{}Z", show_time);
+
+    DateTime::parse_from_rfc3339(&with_offset)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
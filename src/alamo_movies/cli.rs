@@ -1,46 +1,80 @@
 use super::cinema::Cinema;
+use super::config::Config;
 use super::film::Film;
 use super::db;
 use super::error::{NoCalendarFile, ExpiredCalendarFile};
 use super::printer;
 
+use std::collections::{HashMap, HashSet};
 use std::process::exit;
 use std::path::PathBuf;
 use std::fs;
 use std::error::Error;
+use std::thread;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use clap::{ArgMatches};
 use rayon::prelude::*;
 
 pub fn subcommand_films(matches: &ArgMatches) {
-    let cinema_id = matches.value_of("cinema_id").unwrap();
-    let cinema_id = Cinema::to_cinema_id(cinema_id).unwrap();
+    let config = Config::load();
+    let cinema_id = match resolve_cinema_id(&config, matches) {
+        Some(cinema_id) => cinema_id,
+        None => {
+            eprintln!("No cinema ID given and no default_cinema_id configured.");
+            exit(1);
+        }
+    };
+    let cinema_id = Cinema::to_cinema_id(&cinema_id).unwrap();
 
-    let as_json = matches.is_present("json");
+    let mut films = films_for(&config, &cinema_id);
 
-    let films = if let Some(film_type) = matches.value_of("type") {
-        filtered_films_for(&cinema_id, film_type)
-    } else {
-        films_for(&cinema_id)
-    };
+    if let Some(film_type) = matches.value_of("type") {
+        films = filter_by_type(films, film_type);
+    }
 
-    if as_json {
-        printer::json_list_films(&films);
-    } else {
-        printer::list_films(&films);
+    if let Some(query) = matches.value_of("search") {
+        films = filter_by_search(films, query);
+    }
+
+    let after = parse_date_bound_arg(matches, "after");
+    // `--before` names a whole day, so the bound is the end of that day.
+    let before = parse_date_bound_arg(matches, "before")
+        .map(|bound| bound + chrono::Duration::days(1) - chrono::Duration::nanoseconds(1));
+
+    if after.is_some() || before.is_some() {
+        films = filter_by_date_range(films, after, before);
+    }
+
+    match matches.value_of("format") {
+        Some("ical") => {
+            let (cinema, _films) = load_or_sync_cinema_for_id(&config, &cinema_id).expect("Failed to load cinema file.");
+
+            printer::ical_showtimes(&cinema, &films);
+        },
+        #[cfg(feature = "rss")]
+        Some("rss") => {
+            let (cinema, _films) = load_or_sync_cinema_for_id(&config, &cinema_id).expect("Failed to load cinema file.");
+
+            printer::rss_list_films(&cinema, &films);
+        },
+        Some("json") => printer::json_list_films(&films),
+        _ if matches.is_present("json") => printer::json_list_films(&films),
+        _ => printer::list_films(&films),
     }
 }
 
 pub fn subcommand_cinema(matches: &ArgMatches) {
+    let config = Config::load();
     let as_json = matches.is_present("json");
 
-    match matches.value_of("cinema_id") {
+    match resolve_cinema_id(&config, matches) {
         Some(cinema_id) => {
-            // the user passed a cinema ID
+            // we have a cinema ID (given explicitly or from config)
             // so find that cinema and print it.
             let cinema_id = Cinema::to_cinema_id(&cinema_id).unwrap();
-            let (cinema, _films) = load_or_sync_cinema_for_id(&cinema_id).expect("Failed to load cinema file.");
+            let (cinema, _films) = load_or_sync_cinema_for_id(&config, &cinema_id).expect("Failed to load cinema file.");
 
             if as_json {
                 printer::json_cinema_info(&cinema);
@@ -49,9 +83,9 @@ pub fn subcommand_cinema(matches: &ArgMatches) {
             }
         },
         None => {
-            // the user did not pass a cinema ID
+            // no cinema ID given and no default configured
             // so print a list of all cinemas (with other args we got)
-            let cinemas = get_cinema_list(matches);
+            let cinemas = get_cinema_list(&config, matches);
 
             if as_json {
                 printer::json_list_cinemas(&cinemas);
@@ -63,11 +97,12 @@ pub fn subcommand_cinema(matches: &ArgMatches) {
 }
 
 pub fn subcommand_get(matches: &ArgMatches) {
+    let config = Config::load();
     let cinema_id = matches.value_of("cinema_id").unwrap();
     let cinema_id = Cinema::to_cinema_id(cinema_id).unwrap();
 
-    if let Ok(_) = Cinema::sync_file(&cinema_id) {
-        let path = db::calendar_path_for_cinema_id(&cinema_id);
+    if let Ok(_) = Cinema::sync_file(&config, &cinema_id) {
+        let path = db::calendar_path_for_cinema_id(&config, &cinema_id);
         let (cinema, _films) = Cinema::from_calendar_file(path.to_str().unwrap()).expect("cannot load file");
 
         eprintln!("Synced {} {}", cinema.id, cinema.name);
@@ -77,10 +112,12 @@ pub fn subcommand_get(matches: &ArgMatches) {
 }
 
 pub fn subcommand_get_all(matches: &ArgMatches) {
+    let config = Config::load();
+
     let cinema_ids =
         if matches.is_present("update-only") {
             // only update the local files
-            let path = db::base_directory_path();
+            let path = db::base_directory_path(&config);
 
             if ! path.is_dir() {
                 eprintln!("No local cinema data to update.");
@@ -95,32 +132,140 @@ pub fn subcommand_get_all(matches: &ArgMatches) {
                 .collect()
         };
 
-    let mut error_count = 0;
+    let sync_all = || {
+        cinema_ids.par_iter()
+            .map(|cinema_id| (cinema_id.clone(), Cinema::sync_file(&config, cinema_id).map_err(|e| e.to_string())))
+            .collect::<Vec<(String, Result<(Cinema, Vec<Film>), String>)>>()
+    };
 
-    for cinema_id in cinema_ids.iter() {
-        error_count = error_count + match Cinema::sync_file(cinema_id) {
-            Err(error) => {
-                eprintln!("Failed to sync cinema {}: {}", cinema_id, error);
-                1
-            },
-            Ok((cinema, _films)) => {
-                eprintln!("Synced cinema {} {}", cinema.id, cinema.name);
-                0
-            },
+    let results = match matches.value_of("jobs").and_then(|jobs| jobs.parse().ok()) {
+        Some(jobs) => {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()
+                .expect("Failed to build thread pool")
+                .install(sync_all)
+        },
+        None => sync_all(),
+    };
+
+    for (cinema_id, result) in results.iter() {
+        match result {
+            Ok((cinema, _films)) => eprintln!("Synced cinema {} {}", cinema.id, cinema.name),
+            Err(error) => eprintln!("Failed to sync cinema {}: {}", cinema_id, error),
         }
     }
 
-    if error_count > 0 {
+    let synced_count = results.iter().filter(|(_, result)| result.is_ok()).count();
+    let failures: Vec<(&String, &String)> = results.iter()
+        .filter_map(|(cinema_id, result)| result.as_ref().err().map(|error| (cinema_id, error)))
+        .collect();
+
+    eprintln!("Synced {}/{} cinemas.", synced_count, results.len());
+
+    if !failures.is_empty() {
+        eprintln!("Failed cinemas:");
+
+        for (cinema_id, error) in failures.iter() {
+            eprintln!("  {}: {}", cinema_id, error);
+        }
+
         exit(1);
     }
 }
 
+/// A film's stable identity across syncs: its ID plus show type, since the
+/// same film can play in more than one format (e.g. a 70mm re-release).
+type FilmKey = (String, String);
+
+pub fn subcommand_watch(matches: &ArgMatches) {
+    let config = Config::load();
+    let as_json = matches.is_present("json");
+
+    let interval_secs: u64 = matches.value_of("interval")
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(300);
+
+    let cinema_ids = watched_cinema_ids(&config, matches);
+
+    if cinema_ids.is_empty() {
+        eprintln!("No cinemas to watch.");
+        return;
+    }
+
+    let mut seen: HashMap<String, HashSet<FilmKey>> = HashMap::new();
+
+    loop {
+        for cinema_id in cinema_ids.iter() {
+            match Cinema::sync_file(&config, cinema_id) {
+                Ok((_cinema, films)) => {
+                    let current: HashSet<FilmKey> = films.iter()
+                        .map(|f| (f.id.clone(), f.show_type.clone()))
+                        .collect();
+
+                    if let Some(previous) = seen.get(cinema_id) {
+                        let added: Vec<&Film> = films.iter()
+                            .filter(|f| !previous.contains(&(f.id.clone(), f.show_type.clone())))
+                            .collect();
+
+                        let removed: Vec<&FilmKey> = previous.iter()
+                            .filter(|key| !current.contains(*key))
+                            .collect();
+
+                        if as_json {
+                            printer::json_list_films(&added.into_iter().cloned().collect::<Vec<Film>>());
+                        } else {
+                            for film in added.iter() {
+                                println!("[{}] + {} ({})", cinema_id, film.name, film.show_type);
+                            }
+                        }
+
+                        for (film_id, show_type) in removed {
+                            eprintln!("[{}] - {} ({})", cinema_id, film_id, show_type);
+                        }
+                    }
+
+                    seen.insert(cinema_id.clone(), current);
+                },
+                Err(error) => {
+                    eprintln!("Failed to sync cinema {}: {}", cinema_id, error);
+                },
+            }
+        }
+
+        thread::sleep(Duration::from_secs(interval_secs));
+    }
+}
+
+fn watched_cinema_ids(config: &Config, matches: &ArgMatches) -> Vec<String> {
+    if matches.is_present("local") {
+        let path = db::base_directory_path(config);
+
+        if ! path.is_dir() {
+            return vec![];
+        }
+
+        db::list_cinema_ids(path)
+    } else {
+        Cinema::list()
+            .iter()
+            .map(|c| c.id.clone())
+            .collect()
+    }
+}
+
+fn resolve_cinema_id(config: &Config, matches: &ArgMatches) -> Option<String> {
+    matches.value_of("cinema_id")
+        .map(String::from)
+        .or_else(|| config.default_cinema_id.clone())
+}
+
 // XXX this should be a Result and not exit.
-fn load_or_sync_cinema_for_id(cinema_id: &str) -> Option<(Cinema, Vec<Film>)> {
-    let path = db::calendar_path_for_cinema_id(cinema_id);
+fn load_or_sync_cinema_for_id(config: &Config, cinema_id: &str) -> Option<(Cinema, Vec<Film>)> {
+    let path = db::calendar_path_for_cinema_id(config, cinema_id);
 
-    if let Err(_) = check_local_file(&path) {
-        match Cinema::sync_file(cinema_id) {
+    if let Err(_) = check_local_file(config, &path) {
+        match Cinema::sync_file(config, cinema_id) {
             Err(error) => {
                 eprintln!("Failed to download cinema data for cinema with ID {}: {}", cinema_id, error);
                 eprintln!("Is this a valid cinema ID?");
@@ -139,7 +284,7 @@ fn load_or_sync_cinema_for_id(cinema_id: &str) -> Option<(Cinema, Vec<Film>)> {
     }
 }
 
-fn check_local_file(path: &PathBuf) -> Result<(), Box<dyn Error>> {
+fn check_local_file(config: &Config, path: &PathBuf) -> Result<(), Box<dyn Error>> {
     // if there's no file, then it's no good
     if ! path.is_file() {
         return Err(Box::new(NoCalendarFile::from_path(path.to_str().unwrap())));
@@ -157,16 +302,16 @@ fn check_local_file(path: &PathBuf) -> Result<(), Box<dyn Error>> {
 
     let duration = now.signed_duration_since(parsed_date);
 
-    // check the duration. make sure it's not older than 24 hours.
-    if duration.num_hours() > 24 {
+    // check the duration against the configured TTL.
+    if duration.num_hours() > config.cache_ttl_hours {
         return Err(Box::new(ExpiredCalendarFile::from_date_time(&date_time)));
     }
 
     Ok(())
 }
 
-fn films_for(cinema_id: &str) -> Vec<Film> {
-    match load_or_sync_cinema_for_id(cinema_id) {
+fn films_for(config: &Config, cinema_id: &str) -> Vec<Film> {
+    match load_or_sync_cinema_for_id(config, cinema_id) {
         Some((_cinema, mut films)) => {
             // list it out
             films.sort_by(|a,b| a.name.cmp(&b.name));
@@ -180,27 +325,54 @@ fn films_for(cinema_id: &str) -> Vec<Film> {
     }
 }
 
-fn filtered_films_for(cinema_id: &str, film_type: &str) -> Vec<Film> {
-    match load_or_sync_cinema_for_id(cinema_id) {
-        Some((_cinema, mut films)) => {
-            // list it out
-            films.sort_by(|a,b| a.name.cmp(&b.name));
+fn filter_by_type(films: Vec<Film>, film_type: &str) -> Vec<Film> {
+    let film_type = film_type.to_lowercase();
 
-            films.iter()
-                .filter(|f| f.show_type.to_lowercase() == film_type.to_lowercase() )
-                .cloned()
-                .collect()
-        },
-        None => {
-            eprintln!("Failed to load cinema file.");
-            vec![]
-        },
+    films.into_iter()
+        .filter(|f| f.show_type.to_lowercase() == film_type)
+        .collect()
+}
+
+fn filter_by_search(films: Vec<Film>, query: &str) -> Vec<Film> {
+    let query = query.to_lowercase();
+
+    films.into_iter()
+        .filter(|f| f.name.to_lowercase().contains(&query))
+        .collect()
+}
+
+/// Keeps only films with at least one showtime on or after `after` and on
+/// or before `before` (either bound may be omitted).
+fn filter_by_date_range(films: Vec<Film>, after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Vec<Film> {
+    films.into_iter()
+        .filter(|f| f.showtimes.iter().any(|showtime| {
+            after.is_none_or(|bound| showtime.starts_at >= bound) &&
+                before.is_none_or(|bound| showtime.starts_at <= bound)
+        }))
+        .collect()
+}
+
+/// Parses a `YYYY-MM-DD` bound into midnight UTC on that date.
+fn parse_date_bound(date: &str) -> Option<DateTime<Utc>> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .map(|date| DateTime::from_utc(date.and_hms(0, 0, 0), Utc))
+}
+
+fn parse_date_bound_arg(matches: &ArgMatches, name: &str) -> Option<DateTime<Utc>> {
+    let raw = matches.value_of(name)?;
+    let parsed = parse_date_bound(raw);
+
+    if parsed.is_none() {
+        eprintln!("Ignoring --{}: {:?} is not a valid date (expected YYYY-MM-DD)", name, raw);
     }
+
+    parsed
 }
 
-fn get_cinema_list(matches: &ArgMatches) -> Vec<Cinema> {
+fn get_cinema_list(config: &Config, matches: &ArgMatches) -> Vec<Cinema> {
     if matches.is_present("local") {
-        let db_path = db::base_directory_path();
+        let db_path = db::base_directory_path(config);
 
         if ! db_path.is_dir() {
             return vec![];
@@ -212,7 +384,7 @@ fn get_cinema_list(matches: &ArgMatches) -> Vec<Cinema> {
             cinema_ids
                 .par_iter()
                 .map(|cinema_id| {
-                    let (cinema, _films) = load_or_sync_cinema_for_id(&cinema_id).expect("Failed to load cinema file.");
+                    let (cinema, _films) = load_or_sync_cinema_for_id(config, &cinema_id).expect("Failed to load cinema file.");
 
                     cinema
                 })
@@ -226,4 +398,3 @@ fn get_cinema_list(matches: &ArgMatches) -> Vec<Cinema> {
         Cinema::list().to_vec()
     }
 }
-
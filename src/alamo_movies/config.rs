@@ -0,0 +1,68 @@
+use super::error::InvalidConfigFile;
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+/// Settings read from `~/.alamo/config.json`. Any field missing from the
+/// file (or the file itself missing) falls back to its default.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub cache_ttl_hours: i64,
+    pub data_dir: Option<PathBuf>,
+    pub default_cinema_id: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            cache_ttl_hours: 24,
+            data_dir: None,
+            default_cinema_id: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        let home_dir = match env::var("HOME") {
+            Ok(home) => home,
+            _ => String::from(""),
+        };
+
+        PathBuf::from(home_dir)
+            .join(".alamo")
+            .join("config.json")
+    }
+
+    /// Loads the config file, falling back to defaults if it's missing or
+    /// malformed.
+    pub fn load() -> Config {
+        Self::load_from(&Self::path()).unwrap_or_else(|error| {
+            eprintln!("Warning: {}", error);
+
+            Config::default()
+        })
+    }
+
+    fn load_from(path: &PathBuf) -> Result<Config, Box<dyn Error>> {
+        if !path.is_file() {
+            return Ok(Config::default());
+        }
+
+        let contents = fs::read_to_string(path)?;
+        let v: Value = serde_json::from_str(&contents)
+            .map_err(|_| InvalidConfigFile::from_path(path.to_str().unwrap()))?;
+
+        let default_config = Config::default();
+
+        Ok(Config {
+            cache_ttl_hours: v["cache_ttl_hours"].as_i64().unwrap_or(default_config.cache_ttl_hours),
+            data_dir: v["data_dir"].as_str().map(PathBuf::from),
+            default_cinema_id: v["default_cinema_id"].as_str().map(String::from),
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use super::config::Config;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use regex::Regex;
+
+/// Returns the directory that holds synced `.calendar.json` files. Honors
+/// `config.data_dir` if set, otherwise defaults to `~/.alamo/db`.
+pub fn base_directory_path(config: &Config) -> PathBuf {
+    if let Some(data_dir) = &config.data_dir {
+        return data_dir.clone();
+    }
+
+    let home_dir = match env::var("HOME") {
+        Ok(home) => home,
+        _ => String::from(""),
+    };
+
+    PathBuf::from(home_dir)
+        .join(".alamo")
+        .join("db")
+}
+
+pub fn calendar_path_for_cinema_id(config: &Config, cinema_id: &str) -> PathBuf {
+    base_directory_path(config).join(format!("{}.calendar.json", cinema_id))
+}
+
+pub fn list_cinema_ids(path: PathBuf) -> Vec<String> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^(.+)\.calendar\.json$").unwrap();
+    }
+
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.path().is_dir())
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_str()?;
+
+            RE.captures(file_name)
+                .map(|captures| String::from(&captures[1]))
+        })
+        .collect()
+}
@@ -0,0 +1,59 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct NoCalendarFile {
+    path: String,
+}
+
+impl NoCalendarFile {
+    pub fn from_path(path: &str) -> Self {
+        NoCalendarFile { path: String::from(path) }
+    }
+}
+
+impl fmt::Display for NoCalendarFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "No calendar file found at {}", self.path)
+    }
+}
+
+impl Error for NoCalendarFile {}
+
+#[derive(Debug)]
+pub struct ExpiredCalendarFile {
+    feed_generated: String,
+}
+
+impl ExpiredCalendarFile {
+    pub fn from_date_time(feed_generated: &str) -> Self {
+        ExpiredCalendarFile { feed_generated: String::from(feed_generated) }
+    }
+}
+
+impl fmt::Display for ExpiredCalendarFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Calendar file is stale (generated {})", self.feed_generated)
+    }
+}
+
+impl Error for ExpiredCalendarFile {}
+
+#[derive(Debug)]
+pub struct InvalidConfigFile {
+    path: String,
+}
+
+impl InvalidConfigFile {
+    pub fn from_path(path: &str) -> Self {
+        InvalidConfigFile { path: String::from(path) }
+    }
+}
+
+impl fmt::Display for InvalidConfigFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not parse config file at {}", self.path)
+    }
+}
+
+impl Error for InvalidConfigFile {}
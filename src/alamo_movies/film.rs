@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone)]
+pub struct Showtime {
+    pub session_id: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Film {
+    pub id: String,
+    pub name: String,
+    pub show_type: String,
+    pub showtimes: Vec<Showtime>,
+}
@@ -0,0 +1,7 @@
+pub mod cli;
+pub mod cinema;
+pub mod config;
+pub mod film;
+pub mod db;
+pub mod error;
+pub mod printer;
@@ -0,0 +1,131 @@
+use super::cinema::Cinema;
+use super::film::Film;
+
+pub fn list_films(films: &[Film]) {
+    for film in films.iter() {
+        println!("{}", film.name);
+    }
+}
+
+pub fn json_list_films(films: &[Film]) {
+    let names: Vec<&str> = films.iter().map(|f| f.name.as_str()).collect();
+
+    println!("{}", serde_json::to_string(&names).unwrap());
+}
+
+pub fn cinema_info(cinema: &Cinema) {
+    println!("{} {} ({})", cinema.id, cinema.name, cinema.market.name);
+}
+
+pub fn json_cinema_info(cinema: &Cinema) {
+    let info = serde_json::json!({
+        "id": cinema.id,
+        "name": cinema.name,
+        "market": cinema.market.name,
+    });
+
+    println!("{}", info);
+}
+
+pub fn list_cinemas(cinemas: &[Cinema]) {
+    for cinema in cinemas.iter() {
+        cinema_info(cinema);
+    }
+}
+
+pub fn json_list_cinemas(cinemas: &[Cinema]) {
+    for cinema in cinemas.iter() {
+        json_cinema_info(cinema);
+    }
+}
+
+/// Renders `films` (with their parsed showtimes) as an RFC 5545 VCALENDAR,
+/// one VEVENT per showtime, suitable for subscribing to in a calendar app.
+///
+/// Content lines are terminated with CRLF per RFC 5545 §3.1, not the
+/// platform line ending `println!` would give us.
+pub fn ical_showtimes(cinema: &Cinema, films: &[Film]) {
+    ical_line("BEGIN:VCALENDAR");
+    ical_line("VERSION:2.0");
+    ical_line("PRODID:-//alamo-movies//EN");
+
+    let now = format_ical_datetime(&chrono::Utc::now());
+
+    for film in films.iter() {
+        for showtime in film.showtimes.iter() {
+            ical_line("BEGIN:VEVENT");
+            ical_line(&format!("UID:{}-{}@alamo-movies", cinema.id, showtime.session_id));
+            ical_line(&format!("DTSTAMP:{}", now));
+            ical_line(&format!("DTSTART:{}", format_ical_datetime(&showtime.starts_at)));
+            ical_line(&format!("DTEND:{}", format_ical_datetime(&showtime.ends_at)));
+            ical_line(&format!("SUMMARY:{}", escape_ical_text(&film.name)));
+            ical_line(&format!("LOCATION:{}", escape_ical_text(&format!("{} ({})", cinema.name, cinema.market.name))));
+            ical_line("END:VEVENT");
+        }
+    }
+
+    ical_line("END:VCALENDAR");
+}
+
+fn ical_line(line: &str) {
+    print!("{}\r\n", line);
+}
+
+fn format_ical_datetime(dt: &chrono::DateTime<chrono::Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+}
+
+/// Renders `films` as an RSS 2.0 `<channel>` titled after `cinema`, one
+/// `<item>` per film, so users can poll a cinema for new titles in any
+/// feed reader.
+#[cfg(feature = "rss")]
+pub fn rss_list_films(cinema: &Cinema, films: &[Film]) {
+    use quick_xml::events::{BytesEnd, BytesStart, Event};
+    use quick_xml::Writer;
+    use std::io::Cursor;
+
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+
+    writer.write_event(Event::Start(BytesStart::new("rss").with_attributes(vec![("version", "2.0")]))).unwrap();
+    writer.write_event(Event::Start(BytesStart::new("channel"))).unwrap();
+
+    write_text_element(&mut writer, "title", &format!("{} Showtimes", cinema.name));
+    write_text_element(&mut writer, "link", &format!("https://drafthouse.com/theater/{}", cinema.id));
+    write_text_element(&mut writer, "description", &format!("Upcoming films at {} ({})", cinema.name, cinema.market.name));
+    write_text_element(&mut writer, "pubDate", &cinema.feed_generated.to_rfc2822());
+
+    for film in films.iter() {
+        writer.write_event(Event::Start(BytesStart::new("item"))).unwrap();
+
+        write_text_element(&mut writer, "title", &film.name);
+        write_text_element(&mut writer, "category", &film.show_type);
+
+        writer.write_event(Event::Start(BytesStart::new("guid").with_attributes(vec![("isPermaLink", "false")]))).unwrap();
+        writer.write_event(Event::Text(quick_xml::events::BytesText::new(&format!("{}-{}", cinema.id, film.id)))).unwrap();
+        writer.write_event(Event::End(BytesEnd::new("guid"))).unwrap();
+
+        writer.write_event(Event::End(BytesEnd::new("item"))).unwrap();
+    }
+
+    writer.write_event(Event::End(BytesEnd::new("channel"))).unwrap();
+    writer.write_event(Event::End(BytesEnd::new("rss"))).unwrap();
+
+    let bytes = writer.into_inner().into_inner();
+
+    println!("{}", String::from_utf8(bytes).unwrap());
+}
+
+#[cfg(feature = "rss")]
+fn write_text_element<W: std::io::Write>(writer: &mut quick_xml::Writer<W>, name: &str, text: &str) {
+    use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+
+    writer.write_event(Event::Start(BytesStart::new(name))).unwrap();
+    writer.write_event(Event::Text(BytesText::new(text))).unwrap();
+    writer.write_event(Event::End(BytesEnd::new(name))).unwrap();
+}
@@ -1,17 +1,16 @@
 mod alamo_movies;
-use crate::alamo_movies::cinema::Cinema;
+use alamo_movies::cli;
 
 #[macro_use] extern crate lazy_static;
 extern crate regex;
 
-use regex::Regex;
-
 extern crate clap;
 use clap::{Arg, App, SubCommand};
 
-use std::fs;
-use std::env;
-use std::path::{PathBuf};
+#[cfg(feature = "rss")]
+const FILM_FORMATS: &[&str] = &["list", "json", "ical", "rss"];
+#[cfg(not(feature = "rss"))]
+const FILM_FORMATS: &[&str] = &["list", "json", "ical"];
 
 fn main() {
     let matches = App::new("Alamo Movies")
@@ -21,9 +20,41 @@ fn main() {
         .subcommand(SubCommand::with_name("films")
                     .about("List films for the given theater")
                     .arg(Arg::with_name("cinema_id")
-                         .help("The ID of the cinema from which to list upcoming films.")
-                         .required(true)
+                         .help("The ID of the cinema from which to list upcoming films. Falls back to default_cinema_id in config if omitted.")
+                         .required(false)
                         )
+                    .arg(Arg::with_name("type")
+                         .help("Only list films of the given show type")
+                         .short("t")
+                         .long("type")
+                         .takes_value(true)
+                         )
+                    .arg(Arg::with_name("json")
+                         .help("Print the film list as JSON")
+                         .long("json")
+                         .takes_value(false)
+                         )
+                    .arg(Arg::with_name("format")
+                         .help("Output format for the film list")
+                         .long("format")
+                         .takes_value(true)
+                         .possible_values(FILM_FORMATS)
+                         )
+                    .arg(Arg::with_name("search")
+                         .help("Only list films whose name contains this (case-insensitive)")
+                         .long("search")
+                         .takes_value(true)
+                         )
+                    .arg(Arg::with_name("after")
+                         .help("Only list films with a showtime on or after this date (YYYY-MM-DD)")
+                         .long("after")
+                         .takes_value(true)
+                         )
+                    .arg(Arg::with_name("before")
+                         .help("Only list films with a showtime on or before this date (YYYY-MM-DD)")
+                         .long("before")
+                         .takes_value(true)
+                         )
                     )
         .subcommand(SubCommand::with_name("cinema")
                     .about("List available cinemas.")
@@ -34,6 +65,11 @@ fn main() {
                          .long("local")
                          .takes_value(false)
                          )
+                    .arg(Arg::with_name("json")
+                         .help("Print cinema info as JSON")
+                         .long("json")
+                         .takes_value(false)
+                         )
                     .arg(Arg::with_name("cinema_id")
                          .help("The ID of the cinema to get info about")
                          .required(false)
@@ -46,116 +82,50 @@ fn main() {
                          .required(true)
                          )
                     )
+        .subcommand(SubCommand::with_name("get-all")
+                    .about("Fetch all known cinemas")
+                    .arg(Arg::with_name("update-only")
+                         .help("Only re-sync cinemas that already have local data")
+                         .long("update-only")
+                         .takes_value(false)
+                         )
+                    .arg(Arg::with_name("jobs")
+                         .help("Max number of cinemas to sync concurrently")
+                         .short("j")
+                         .long("jobs")
+                         .takes_value(true)
+                         )
+                    )
+        .subcommand(SubCommand::with_name("watch")
+                    .about("Periodically re-sync cinemas and report newly-appeared films")
+                    .arg(Arg::with_name("local")
+                         .help("Watch only locally-cached cinemas instead of the built-in list")
+                         .short("l")
+                         .long("local")
+                         .takes_value(false)
+                         )
+                    .arg(Arg::with_name("interval")
+                         .help("Seconds between sync cycles (default: 300)")
+                         .long("interval")
+                         .takes_value(true)
+                         )
+                    .arg(Arg::with_name("json")
+                         .help("Print newly-appeared films as JSON")
+                         .long("json")
+                         .takes_value(false)
+                         )
+                    )
         .get_matches();
 
     if let Some(matches) = matches.subcommand_matches("films") {
-        let cinema_id = matches.value_of("cinema_id").unwrap();
-
-        list_films_for(cinema_id);
+        cli::subcommand_films(matches);
     } else if let Some(matches) = matches.subcommand_matches("cinema") {
-        match matches.value_of("cinema_id") {
-            Some(cinema_id) => 
-                print_cinema_info_for(cinema_id),
-            None =>
-                print_cinema_list(matches),
-        }
+        cli::subcommand_cinema(matches);
     } else if let Some(matches) = matches.subcommand_matches("get") {
-        let cinema_id = matches.value_of("cinema_id").unwrap();
-
-        if let Ok(_) = Cinema::sync_file(cinema_id) {
-            let path = Cinema::get_file_path_for(cinema_id);
-            let (cinema, _films) = Cinema::from_calendar_file(path.to_str().unwrap()).expect("cannot load file");
-
-            println!("Synced {} {}", cinema.id, cinema.name);
-        } else {
-            panic!("Error");
-        }
+        cli::subcommand_get(matches);
+    } else if let Some(matches) = matches.subcommand_matches("get-all") {
+        cli::subcommand_get_all(matches);
+    } else if let Some(matches) = matches.subcommand_matches("watch") {
+        cli::subcommand_watch(matches);
     }
 }
-
-fn list_films_for(cinema_id: &str) {
-    // first, read the file into a string
-    let path = Cinema::get_file_path_for(cinema_id);
-
-    // if the file does not exist, then download it.
-    if ! path.is_file() {
-        match Cinema::sync_file(cinema_id) {
-            Err(_) => panic!("Failed to get cinema file for id: {}", cinema_id),
-            _ => eprintln!("Fetched new file for id: {}", cinema_id),
-        }
-    }
-
-    let (cinema, films) = Cinema::from_calendar_file(path.to_str().unwrap()).expect("cannot load file");
-
-    // list it out
-    for movie in films.iter() {
-        println!("{}", movie.name);
-    }
-}
-
-fn print_cinema_info_for(cinema_id: &str) {
-    let path = Cinema::get_file_path_for(cinema_id);
-
-    print_cinema_info_for_file(path.to_str().unwrap());
-}
-
-fn print_cinema_info_for_file(path: &str) {
-    let (cinema, _films) = Cinema::from_calendar_file(path).expect("cannot load file");
-
-    println!("{} {} ({})", cinema.id, cinema.name, cinema.market.name);
-}
-
-fn print_cinema_list(matches: &clap::ArgMatches) {
-
-    let local_only: bool = matches.occurrences_of("local") > 0;
-
-    if local_only {
-        let home_dir = match env::var("HOME") {
-            Ok(home) => home,
-            _ => String::from(""),
-        };
-
-        let mut db_path = PathBuf::from(home_dir);
-        db_path = db_path
-            .join(".alamo")
-            .join("db");
-
-        for file in get_cinema_files(db_path) {
-            print_cinema_info_for_file(file.to_str().unwrap());
-        }
-    } else {
-        // print out the built-in cinema list
-        let cinemas = Cinema::list();
-
-        for cinema in cinemas.iter() {
-            println!("{} {} ({})", cinema.id, cinema.name, cinema.market.name);
-        }
-    }
-}
-
-fn get_cinema_files(path: PathBuf) -> Vec<PathBuf> {
-    fs::read_dir(path)
-        .unwrap()
-        .filter(|entry| {
-            match entry {
-                Ok(entry) => !entry.path().is_dir() && is_calendar_file(entry.path()),
-                _ => false,
-            }
-        })
-        .map(|entry| {
-            if let Ok(entry) = entry {
-                entry.path()
-            } else {
-                panic!("This shouldn't happen")
-            }
-        })
-        .collect()
-}
-
-fn is_calendar_file(path: PathBuf) -> bool {
-    lazy_static! {
-         static ref RE: Regex = Regex::new(r"\.calendar\.json$").unwrap();
-    }
-
-    RE.is_match(path.to_str().unwrap())
-}